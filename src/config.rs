@@ -0,0 +1,97 @@
+// config.rs
+
+//! Runtime configuration for the embedding/Qdrant pipeline.
+//!
+//! The model, collection name, vector dimension, and distance metric used to be
+//! hardcoded across `initialize_model`, `setup_qdrant_collection`, and their
+//! callers, so switching embedding models could silently create a collection with
+//! the wrong dimension. [`PipelineConfig`] collects those knobs in one place and
+//! derives the vector dimension from the chosen model instead of a literal `384`.
+
+use fastembed::EmbeddingModel;
+use qdrant_client::qdrant::Distance;
+
+/// Runtime configuration threaded through the embedding/Qdrant pipeline.
+///
+/// Build one with [`PipelineConfig::new`] (or [`Default::default`] for the
+/// original `real_estate` setup) and pass it to [`initialize_model`](crate::controller::initialize_model)
+/// and the `setup_*_collection` functions so the model and its collection never
+/// drift out of sync.
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    pub model: EmbeddingModel,
+    pub collection_name: String,
+    pub distance: Distance,
+    pub quantization: bool,
+}
+
+impl PipelineConfig {
+    /// Builds a config for `model`, writing into `collection_name`, with sensible
+    /// defaults (cosine distance, scalar quantization enabled) for the rest.
+    pub fn new(model: EmbeddingModel, collection_name: impl Into<String>) -> Self {
+        Self {
+            model,
+            collection_name: collection_name.into(),
+            distance: Distance::Cosine,
+            quantization: true,
+        }
+    }
+
+    /// The vector dimension produced by `self.model`.
+    ///
+    /// Covers the FastEmbed models this crate has been exercised against. An
+    /// unrecognized model panics rather than guessing a dimension, since a wrong
+    /// guess would silently create a collection sized for the wrong model — add the
+    /// new model's dimension here instead of trusting a fallback.
+    pub fn dimension(&self) -> u64 {
+        match &self.model {
+            EmbeddingModel::AllMiniLML6V2 | EmbeddingModel::AllMiniLML6V2Q => 384,
+            EmbeddingModel::AllMiniLML12V2 | EmbeddingModel::AllMiniLML12V2Q => 384,
+            EmbeddingModel::BGESmallENV15 | EmbeddingModel::BGESmallENV15Q => 384,
+            EmbeddingModel::BGESmallZHV15 => 512,
+            EmbeddingModel::BGEBaseENV15 | EmbeddingModel::BGEBaseENV15Q => 768,
+            EmbeddingModel::BGELargeENV15 | EmbeddingModel::BGELargeENV15Q => 1024,
+            EmbeddingModel::GTEBaseENV15 | EmbeddingModel::GTEBaseENV15Q => 768,
+            EmbeddingModel::NomicEmbedTextV15 | EmbeddingModel::NomicEmbedTextV15Q => 768,
+            EmbeddingModel::MxbaiEmbedLargeV1 | EmbeddingModel::MxbaiEmbedLargeV1Q => 1024,
+            other => panic!(
+                "PipelineConfig::dimension: no known vector dimension for model {other:?}; \
+                 add it to this match instead of guessing"
+            ),
+        }
+    }
+}
+
+impl Default for PipelineConfig {
+    /// The crate's original configuration: `AllMiniLML6V2` into `real_estate`.
+    fn default() -> Self {
+        Self::new(EmbeddingModel::AllMiniLML6V2, "real_estate")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dimension_matches_model() {
+        let config = PipelineConfig::new(EmbeddingModel::BGEBaseENV15, "docs");
+        assert_eq!(config.dimension(), 768);
+    }
+
+    #[test]
+    fn test_default_matches_original_hardcoded_setup() {
+        let config = PipelineConfig::default();
+        assert_eq!(config.collection_name, "real_estate");
+        assert_eq!(config.dimension(), 384);
+        assert_eq!(config.distance, Distance::Cosine);
+        assert!(config.quantization);
+    }
+
+    #[test]
+    #[should_panic(expected = "no known vector dimension")]
+    fn test_dimension_panics_on_unlisted_model() {
+        let config = PipelineConfig::new(EmbeddingModel::ClipVitB32, "docs");
+        config.dimension();
+    }
+}
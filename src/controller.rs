@@ -1,28 +1,108 @@
-use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+use crate::config::PipelineConfig;
+use crate::retry::{classify_embedding_error, with_retry, with_retry_blocking};
+use crate::sparse::{compute_sparse_vector, reciprocal_rank_fusion};
+use fastembed::{InitOptions, TextEmbedding};
+use rayon::prelude::*;
 use qdrant_client::qdrant::{
-    CreateCollectionBuilder, Distance, PointStruct, ScalarQuantizationBuilder, SearchParamsBuilder,
-    SearchPointsBuilder, UpsertPointsBuilder, VectorParamsBuilder,
+    CreateCollectionBuilder, NamedVectors, PointStruct, ScalarQuantizationBuilder,
+    SearchParamsBuilder, SearchPointsBuilder, SparseVectorParamsBuilder, SparseVectorsConfigBuilder,
+    UpsertPointsBuilder, VectorParamsBuilder, VectorsConfigBuilder,
 };
 use qdrant_client::{Payload, Qdrant, QdrantError};
 use serde_json::Value;
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::time::Instant;
 
-/// Initializes the FastEmbed model for generating text embeddings.
+/// Name of the collection used to cache previous query results.
+///
+/// Queries whose embedding lands close enough to a cached query (see
+/// [`search_with_cache`]) are answered from this collection instead of
+/// re-searching `real_estate`.
+pub const QUERY_CACHE_COLLECTION: &str = "query_cache";
+
+/// Name of the named dense (semantic) vector in the main collection.
+const DENSE_VECTOR_NAME: &str = "dense";
+
+/// Name of the named sparse (keyword) vector in the main collection.
+const SPARSE_VECTOR_NAME: &str = "sparse";
+
+/// The minimum number of candidates each of the dense and sparse searches
+/// contributes before they are fused with Reciprocal Rank Fusion (a larger
+/// requested limit raises it), and the number of fused results that
+/// [`search_qdrant`] and [`search_with_cache`] ask for.
+const HYBRID_SEARCH_LIMIT: u64 = 10;
+
+/// Maximum number of attempts [`with_retry`] makes before giving up on an
+/// embedding call.
+const MAX_EMBEDDING_ATTEMPTS: u32 = 5;
+
+/// How many batches [`ingest_file`] embeds concurrently.
+const INGEST_CONCURRENCY: usize = 4;
+
+/// An error from Qdrant, the embedding model, or (via [`crate::rag::rag_query`]) a
+/// completion backend.
+///
+/// Functions that both talk to Qdrant and embed text (e.g. [`search_qdrant`])
+/// return this instead of [`QdrantError`] so a failure in either step can still be
+/// reported after [`with_retry`] exhausts its attempts.
+#[derive(Debug)]
+pub enum PipelineError {
+    Qdrant(QdrantError),
+    /// An embedding failure. Completion-backend failures from
+    /// [`crate::rag::rag_query`] are carried here too and display with the same
+    /// "embedding error" prefix.
+    Embedding(anyhow::Error),
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::Qdrant(error) => write!(f, "Qdrant error: {error}"),
+            PipelineError::Embedding(error) => write!(f, "embedding error: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+impl From<QdrantError> for PipelineError {
+    fn from(error: QdrantError) -> Self {
+        PipelineError::Qdrant(error)
+    }
+}
+
+impl From<anyhow::Error> for PipelineError {
+    fn from(error: anyhow::Error) -> Self {
+        PipelineError::Embedding(error)
+    }
+}
+
+/// Embeds `documents`, retrying transient failures with [`with_retry`].
+async fn embed_with_retry(model: &TextEmbedding, documents: Vec<String>) -> Result<Vec<Vec<f32>>, anyhow::Error> {
+    with_retry(MAX_EMBEDDING_ATTEMPTS, classify_embedding_error, || {
+        let documents = documents.clone();
+        async { model.embed(documents, None) }
+    })
+    .await
+}
+
+/// Initializes the FastEmbed model named by `config.model`, with progress feedback.
+///
+/// # Arguments
 ///
-/// This function initializes the `TextEmbedding` model using the `AllMiniLML6V2` model
-/// with progress feedback.
+/// * `config` - The pipeline configuration naming which FastEmbed model to load.
 ///
 /// # Example
 ///
 /// ```
-/// let model = initialize_model();
+/// let model = initialize_model(&PipelineConfig::default());
 /// ```
-pub fn initialize_model() -> TextEmbedding {
+pub fn initialize_model(config: &PipelineConfig) -> TextEmbedding {
     let start_time = Instant::now();
     let model = TextEmbedding::try_new(InitOptions {
-        model_name: EmbeddingModel::AllMiniLML6V2,
+        model_name: config.model.clone(),
         show_download_progress: true,
         ..Default::default()
     })
@@ -32,12 +112,15 @@ pub fn initialize_model() -> TextEmbedding {
     model
 }
 
-/// Sets up a collection in Qdrant, including deleting the existing collection and
-/// creating a new one with a vector size of 384 and cosine distance for embeddings.
+/// Sets up `config.collection_name` in Qdrant, including deleting the existing
+/// collection and creating a new one with `config.model`'s vector dimension and
+/// `config.distance` for embeddings.
 ///
 /// # Arguments
 ///
 /// * `client` - A reference to the `Qdrant` client.
+/// * `config` - The pipeline configuration naming the collection, model dimension,
+///   distance metric, and whether to enable scalar quantization.
 ///
 /// # Returns
 ///
@@ -47,17 +130,59 @@ pub fn initialize_model() -> TextEmbedding {
 ///
 /// ```
 /// let client = Qdrant::from_url("http://localhost:6334").build()?;
-/// setup_qdrant_collection(&client).await?;
+/// setup_qdrant_collection(&client, &PipelineConfig::default()).await?;
 /// ```
-pub async fn setup_qdrant_collection(client: &Qdrant) -> Result<(), QdrantError> {
-    let collection_name = "real_estate";
-    client.delete_collection(collection_name).await?;
+pub async fn setup_qdrant_collection(client: &Qdrant, config: &PipelineConfig) -> Result<(), QdrantError> {
+    client.delete_collection(config.collection_name.as_str()).await?;
+
+    let mut builder = CreateCollectionBuilder::new(config.collection_name.as_str())
+        .vectors_config(VectorsConfigBuilder::default().add_named_vector_params(
+            DENSE_VECTOR_NAME,
+            VectorParamsBuilder::new(config.dimension(), config.distance),
+        ))
+        .sparse_vectors_config(SparseVectorsConfigBuilder::default().add_named_vector_params(
+            SPARSE_VECTOR_NAME,
+            SparseVectorParamsBuilder::default(),
+        ));
+    if config.quantization {
+        builder = builder.quantization_config(ScalarQuantizationBuilder::default());
+    }
+
+    client.create_collection(builder).await?;
+
+    Ok(())
+}
+
+/// Sets up the [`QUERY_CACHE_COLLECTION`] used by [`search_with_cache`], deleting any
+/// existing collection of the same name first.
+///
+/// The cache collection uses `config.model`'s vector dimension and `config.distance`,
+/// since it is keyed on query embeddings produced by the same model as the main
+/// collection.
+///
+/// # Arguments
+///
+/// * `client` - A reference to the `Qdrant` client.
+/// * `config` - The pipeline configuration naming the model dimension and distance
+///   metric to use for the cache collection.
+///
+/// # Returns
+///
+/// A result that indicates if the operation was successful or an error occurred.
+///
+/// # Example
+///
+/// ```
+/// let client = Qdrant::from_url("http://localhost:6334").build()?;
+/// setup_query_cache_collection(&client, &PipelineConfig::default()).await?;
+/// ```
+pub async fn setup_query_cache_collection(client: &Qdrant, config: &PipelineConfig) -> Result<(), QdrantError> {
+    client.delete_collection(QUERY_CACHE_COLLECTION).await?;
 
     client
         .create_collection(
-            CreateCollectionBuilder::new(collection_name)
-                .vectors_config(VectorParamsBuilder::new(384, Distance::Cosine))
-                .quantization_config(ScalarQuantizationBuilder::default()),
+            CreateCollectionBuilder::new(QUERY_CACHE_COLLECTION)
+                .vectors_config(VectorParamsBuilder::new(config.dimension(), config.distance)),
         )
         .await?;
 
@@ -118,26 +243,28 @@ pub fn load_data(filename: &str) -> (Vec<String>, Vec<Value>) {
 ///
 /// # Returns
 ///
-/// A vector of embeddings where each embedding is a vector of 32-bit floats.
+/// A vector of embeddings where each embedding is a vector of 32-bit floats, or the
+/// error returned after [`with_retry`] exhausts [`MAX_EMBEDDING_ATTEMPTS`]. Empty
+/// `documents` yields an empty result rather than panicking.
 ///
 /// # Example
 ///
 /// ```
-/// let embeddings = generate_embeddings(&model, documents);
+/// let embeddings = generate_embeddings(&model, documents).await?;
 /// ```
-pub fn generate_embeddings(
+pub async fn generate_embeddings(
     model: &TextEmbedding,
     documents: Vec<String>,
-) -> Vec<Vec<f32>> {
+) -> Result<Vec<Vec<f32>>, anyhow::Error> {
     let start_time = Instant::now();
-    let embeddings = model
-        .embed(documents, None)
-        .expect("Failed to generate embeddings");
+    let embeddings = embed_with_retry(model, documents).await?;
     println!("Embeddings length: {}", embeddings.len());
-    println!("Embedding dimension: {}", embeddings[0].len());
+    if let Some(first) = embeddings.first() {
+        println!("Embedding dimension: {}", first.len());
+    }
     println!("Embedding generation time: {:?}", start_time.elapsed());
 
-    embeddings
+    Ok(embeddings)
 }
 
 /// Upserts points (documents with embeddings and payloads) into a Qdrant collection.
@@ -149,7 +276,13 @@ pub fn generate_embeddings(
 ///
 /// * `client` - A reference to the `Qdrant` client.
 /// * `collection_name` - The name of the Qdrant collection.
-/// * `embeddings` - A vector of embeddings for the documents.
+/// * `start_id` - The point ID to assign to the first document; subsequent documents
+///   get consecutive IDs. Callers upserting more than one batch (see [`ingest_file`])
+///   pass the running total so IDs stay unique across batches instead of restarting
+///   from zero each time.
+/// * `documents` - The source text each embedding was generated from, used to derive
+///   the accompanying sparse (keyword) vector for hybrid search.
+/// * `embeddings` - A vector of dense embeddings for the documents.
 /// * `payloads` - A vector of payloads corresponding to the documents.
 ///
 /// # Returns
@@ -159,11 +292,13 @@ pub fn generate_embeddings(
 /// # Example
 ///
 /// ```
-/// upsert_points(&client, "real_estate", embeddings, payloads).await?;
+/// upsert_points(&client, "real_estate", 0, &documents, embeddings, payloads).await?;
 /// ```
 pub async fn upsert_points(
     client: &Qdrant,
     collection_name: &str,
+    start_id: u64,
+    documents: &[String],
     embeddings: Vec<Vec<f32>>,
     payloads: Vec<Value>,
 ) -> Result<(), QdrantError> {
@@ -175,7 +310,10 @@ pub async fn upsert_points(
                 .clone()
                 .try_into()
                 .expect("Failed to convert payload");
-            PointStruct::new(i as u64, embedding, payload)
+            let vectors = NamedVectors::default()
+                .add_vector(DENSE_VECTOR_NAME, embedding)
+                .add_vector(SPARSE_VECTOR_NAME, compute_sparse_vector(&documents[i]));
+            PointStruct::new(start_id + i as u64, vectors, payload)
         })
         .collect();
 
@@ -188,6 +326,119 @@ pub async fn upsert_points(
     Ok(())
 }
 
+/// Reads a JSONL file in fixed-size batches, following the same `description`-field
+/// convention as [`load_data`].
+///
+/// Each batch is `(documents, payloads)`, matching [`load_data`]'s tuple shape, so a
+/// batch can be handed straight to [`generate_embeddings`] and [`upsert_points`].
+fn read_batches(filename: &str, batch_size: usize) -> Vec<(Vec<String>, Vec<Value>)> {
+    let file = File::open(filename).expect("Unable to open file - data.jsonl");
+    let reader = BufReader::new(file);
+
+    let mut batches = Vec::new();
+    let mut documents = Vec::new();
+    let mut payloads = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.expect("Unable to read line");
+        let json: Value = serde_json::from_str(&line).expect("Unable to parse JSON");
+
+        if let Some(description) = json.get("description").and_then(|d| d.as_str()) {
+            documents.push(description.to_string());
+            payloads.push(json);
+        } else {
+            eprintln!("No description found for entry: {}", index);
+        }
+
+        if documents.len() == batch_size {
+            batches.push((std::mem::take(&mut documents), std::mem::take(&mut payloads)));
+        }
+    }
+
+    if !documents.is_empty() {
+        batches.push((documents, payloads));
+    }
+
+    batches
+}
+
+/// Ingests a JSONL file into `collection_name` in fixed-size batches, so that no
+/// single embedding call or upsert covers the whole file.
+///
+/// The whole file is first read into batches of `batch_size` documents (see
+/// [`read_batches`]). Every batch is then embedded with rayon, up to
+/// [`INGEST_CONCURRENCY`] batches at a time, since embedding is CPU-bound; only after
+/// all batches are embedded are they upserted, one by one in file order. The file's
+/// documents, payloads and embeddings are therefore all held in memory together, so
+/// peak memory grows with the file size. Point IDs increase monotonically across batches rather than restarting
+/// from zero, so later batches never overwrite earlier ones. Each batch's embedding
+/// call is retried with [`with_retry_blocking`] (rayon workers can't `.await` the
+/// async [`with_retry`]), so a transient failure mid-file doesn't abort the whole
+/// ingestion. The rayon dispatch runs inside `tokio::task::block_in_place` so the
+/// synchronous embedding/retry work doesn't park the async runtime's worker thread.
+///
+/// # Arguments
+///
+/// * `client` - A reference to the `Qdrant` client.
+/// * `model` - A reference to the `TextEmbedding` model.
+/// * `collection_name` - The name of the Qdrant collection to ingest into.
+/// * `path` - The path to the JSONL file to ingest.
+/// * `batch_size` - How many documents to embed and upsert per batch (e.g. `256`).
+///
+/// # Returns
+///
+/// A result indicating the success or failure of the ingestion.
+///
+/// # Example
+///
+/// ```
+/// ingest_file(&client, &model, "real_estate", "data.jsonl", 256).await?;
+/// ```
+pub async fn ingest_file(
+    client: &Qdrant,
+    model: &TextEmbedding,
+    collection_name: &str,
+    path: &str,
+    batch_size: usize,
+) -> Result<(), PipelineError> {
+    let batches = read_batches(path, batch_size);
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(INGEST_CONCURRENCY)
+        .build()
+        .expect("Failed to build embedding thread pool");
+    // `pool.install` blocks the calling thread until every batch is embedded, and
+    // each batch's retry backoff (`with_retry_blocking`) sleeps synchronously too, so
+    // this has to run via `block_in_place` rather than straight on the async task —
+    // otherwise it would park the tokio worker thread driving this future for the
+    // whole embedding run, starving the runtime's other async work.
+    let embedded_batches: Vec<Result<(Vec<String>, Vec<Value>, Vec<Vec<f32>>), anyhow::Error>> =
+        tokio::task::block_in_place(|| {
+            pool.install(|| {
+                batches
+                    .into_par_iter()
+                    .map(|(documents, payloads)| {
+                        let embeddings = with_retry_blocking(MAX_EMBEDDING_ATTEMPTS, classify_embedding_error, || {
+                            model.embed(documents.clone(), None)
+                        })?;
+                        Ok((documents, payloads, embeddings))
+                    })
+                    .collect()
+            })
+        });
+
+    let mut next_point_id = 0u64;
+    for batch in embedded_batches {
+        let (documents, payloads, embeddings) = batch?;
+        let batch_len = embeddings.len() as u64;
+
+        upsert_points(client, collection_name, next_point_id, &documents, embeddings, payloads).await?;
+        next_point_id += batch_len;
+    }
+
+    Ok(())
+}
+
 /// Performs a search on the Qdrant collection for documents similar to the given query.
 ///
 /// This function generates an embedding for the query using the FastEmbed model, and searches the Qdrant
@@ -214,31 +465,18 @@ pub async fn search_qdrant(
     model: &TextEmbedding,
     collection_name: &str,
     query: &str,
-) -> Result<(), QdrantError> {
-    let search_document = vec![query.to_string()];
-    let embedding_for_search = model
-        .embed(search_document, None)
-        .expect("Failed to generate search embedding")[0]
-        .clone();
+) -> Result<(), PipelineError> {
+    let results = fetch_search_results(client, model, collection_name, query, HYBRID_SEARCH_LIMIT as usize).await?;
 
-    let search_result = client
-        .search_points(
-            SearchPointsBuilder::new(collection_name, embedding_for_search, 10)
-                .with_payload(true)
-                .params(SearchParamsBuilder::default().exact(true)),
-        )
-        .await?;
-
-    if let Some(found_point) = search_result.result.into_iter().next() {
-        let payload = found_point.payload;
+    if let Some(payload) = results.into_iter().next() {
         if let Some(description) = payload.get("description") {
-            println!("Found description: {}", description.clone().into_json());
+            println!("Found description: {}", description);
         } else {
             println!("Key 'description' not found in payload: {:?}", payload);
         }
 
         if let Some(link) = payload.get("link") {
-            println!("Found link: {}", link.clone().into_json());
+            println!("Found link: {}", link);
         } else {
             println!("Key 'link' not found in payload: {:?}", payload);
         }
@@ -247,6 +485,179 @@ pub async fn search_qdrant(
     Ok(())
 }
 
+/// Runs a hybrid dense + sparse search against `collection_name` and returns the
+/// top `limit` fused matches' payloads as [`Value`]s.
+///
+/// `query` is embedded both densely (via FastEmbed, for semantic similarity) and
+/// sparsely (via [`compute_sparse_vector`], for exact keyword matches). Each search
+/// returns its own top-`limit` ranking (at least [`HYBRID_SEARCH_LIMIT`], so fusion
+/// always has a reasonable pool to work with even for small `limit`s), and the two
+/// rankings are merged with [`reciprocal_rank_fusion`] so that points doing well on
+/// either signal surface near the top. This is the shared lookup used by
+/// [`search_qdrant`] (which prints the top result), [`search_with_cache`] (which
+/// caches the full result set), and [`rag_query`](crate::rag::rag_query) (which
+/// retrieves context for an LLM).
+pub(crate) async fn fetch_search_results(
+    client: &Qdrant,
+    model: &TextEmbedding,
+    collection_name: &str,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<Value>, PipelineError> {
+    let dense_query_vector = embed_with_retry(model, vec![query.to_string()]).await?[0].clone();
+    let sparse_query_vector = compute_sparse_vector(query);
+    let per_branch_limit = (limit as u64).max(HYBRID_SEARCH_LIMIT);
+
+    let dense_result = client
+        .search_points(
+            SearchPointsBuilder::new(collection_name, dense_query_vector, per_branch_limit)
+                .with_payload(true)
+                .using(DENSE_VECTOR_NAME)
+                .params(SearchParamsBuilder::default().exact(true)),
+        )
+        .await?;
+
+    let sparse_result = client
+        .search_points(
+            SearchPointsBuilder::new(collection_name, sparse_query_vector, per_branch_limit)
+                .with_payload(true)
+                .using(SPARSE_VECTOR_NAME),
+        )
+        .await?;
+
+    let mut payloads_by_id: std::collections::HashMap<u64, Value> = std::collections::HashMap::new();
+    let mut dense_ids = Vec::with_capacity(dense_result.result.len());
+    for point in dense_result.result {
+        let id = point_id_as_u64(&point.id);
+        dense_ids.push(id);
+        payloads_by_id.insert(id, payload_to_json(point.payload));
+    }
+
+    let mut sparse_ids = Vec::with_capacity(sparse_result.result.len());
+    for point in sparse_result.result {
+        let id = point_id_as_u64(&point.id);
+        sparse_ids.push(id);
+        payloads_by_id
+            .entry(id)
+            .or_insert_with(|| payload_to_json(point.payload));
+    }
+
+    let fused = reciprocal_rank_fusion(&dense_ids, &sparse_ids);
+
+    Ok(fused
+        .into_iter()
+        .take(limit)
+        .filter_map(|(id, _score)| payloads_by_id.remove(&id))
+        .collect())
+}
+
+/// Converts a Qdrant payload map into a plain [`Value::Object`].
+fn payload_to_json(payload: impl IntoIterator<Item = (String, qdrant_client::qdrant::Value)>) -> Value {
+    Value::Object(
+        payload
+            .into_iter()
+            .map(|(key, value)| (key, value.into_json()))
+            .collect(),
+    )
+}
+
+/// Extracts the numeric ID from a Qdrant point ID.
+///
+/// Every point in this crate is upserted with a numeric ID (see [`upsert_points`]),
+/// so this always succeeds in practice; UUID-keyed points fall back to `0`.
+fn point_id_as_u64(id: &Option<qdrant_client::qdrant::PointId>) -> u64 {
+    use qdrant_client::qdrant::point_id::PointIdOptions;
+
+    match id.as_ref().and_then(|id| id.point_id_options.as_ref()) {
+        Some(PointIdOptions::Num(n)) => *n,
+        _ => 0,
+    }
+}
+
+/// Searches for `query` via the [`QUERY_CACHE_COLLECTION`] first, falling back to the
+/// main `real_estate`-style collection on a cache miss.
+///
+/// The query is embedded and used to search the cache for a previously-answered,
+/// semantically-equivalent query. If the top cache hit's cosine score is at least
+/// `threshold` (e.g. `0.95`), the cached result set is returned directly. Otherwise
+/// this performs the same hybrid lookup as [`search_qdrant`] (via
+/// [`fetch_search_results`], which embeds the query a second time) against
+/// `collection_name`, then
+/// upserts the query embedding and the serialized result set into the cache so the
+/// next equivalent query is a hit.
+///
+/// # Arguments
+///
+/// * `client` - A reference to the `Qdrant` client.
+/// * `model` - A reference to the `TextEmbedding` model.
+/// * `collection_name` - The name of the main collection to fall back to on a miss.
+/// * `query` - The search query string.
+/// * `threshold` - The minimum cosine score for a cache hit (e.g. `0.95`).
+///
+/// # Returns
+///
+/// The matching payloads, either served from the cache or freshly searched.
+///
+/// # Example
+///
+/// ```
+/// let results = search_with_cache(&client, &model, "real_estate", "3 bed semi in Leeds", 0.95).await?;
+/// ```
+pub async fn search_with_cache(
+    client: &Qdrant,
+    model: &TextEmbedding,
+    collection_name: &str,
+    query: &str,
+    threshold: f32,
+) -> Result<Vec<Value>, PipelineError> {
+    let query_embedding = embed_with_retry(model, vec![query.to_string()]).await?[0].clone();
+
+    let cache_result = client
+        .search_points(
+            SearchPointsBuilder::new(QUERY_CACHE_COLLECTION, query_embedding.clone(), 1)
+                .with_payload(true),
+        )
+        .await?;
+
+    if let Some(cached) = cache_result.result.into_iter().next() {
+        if cached.score >= threshold {
+            println!("Cache hit (score {:.4}) for query: {}", cached.score, query);
+            if let Some(Value::Array(results)) =
+                cached.payload.get("results").map(|v| v.clone().into_json())
+            {
+                return Ok(results);
+            }
+        }
+    }
+
+    println!("Cache miss for query: {}", query);
+    let results = fetch_search_results(client, model, collection_name, query, HYBRID_SEARCH_LIMIT as usize).await?;
+
+    let cache_payload: Payload = serde_json::json!({ "results": results })
+        .try_into()
+        .expect("Failed to convert cache payload");
+    let cache_point_id = hash_query(query);
+    client
+        .upsert_points(UpsertPointsBuilder::new(
+            QUERY_CACHE_COLLECTION,
+            vec![PointStruct::new(cache_point_id, query_embedding, cache_payload)],
+        ))
+        .await?;
+
+    Ok(results)
+}
+
+/// Hashes a query string into a stable point ID so repeated identical queries
+/// overwrite the same cache entry instead of accumulating duplicates.
+fn hash_query(query: &str) -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    query.hash(&mut hasher);
+    hasher.finish()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -258,7 +669,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_initialize_model() {
-        let model = initialize_model();
+        let model = initialize_model(&PipelineConfig::default());
         // Perform an actual check to see if the model is working or initialized
         assert!(model.embed(vec!["test".to_string()], None).is_ok());
     }
@@ -266,7 +677,7 @@ mod tests {
     #[tokio::test]
     async fn test_setup_qdrant_collection() {
         let client = Qdrant::from_url("http://localhost:6334").build().expect("Failed to build Qdrant client");
-        let result = setup_qdrant_collection(&client).await;
+        let result = setup_qdrant_collection(&client, &PipelineConfig::default()).await;
         assert!(result.is_ok());
     }
 
@@ -283,11 +694,43 @@ mod tests {
         assert_eq!(payloads[0], json!({"description": "Test document", "key": "value"}));
     }
 
+    #[test]
+    fn test_read_batches_splits_on_batch_size() {
+        let filename = "test_read_batches.jsonl";
+        let mut file = File::create(filename).expect("Unable to create file");
+        for i in 0..5 {
+            writeln!(file, r#"{{"description": "Document {i}", "key": "value"}}"#).expect("Unable to write to file");
+        }
+
+        let batches = read_batches(filename, 2);
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[0].0.len(), 2);
+        assert_eq!(batches[1].0.len(), 2);
+        assert_eq!(batches[2].0.len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ingest_file() {
+        let client = Qdrant::from_url("http://localhost:6334").build().expect("Failed to build Qdrant client");
+        let model = initialize_model(&PipelineConfig::default());
+        let collection_name = "real_estate";
+        setup_qdrant_collection(&client, &PipelineConfig::default()).await.expect("Failed to set up collection");
+
+        let filename = "test_ingest_file.jsonl";
+        let mut file = File::create(filename).expect("Unable to create file");
+        for i in 0..5 {
+            writeln!(file, r#"{{"description": "Ingested document {i}", "key": "value"}}"#).expect("Unable to write to file");
+        }
+
+        let result = ingest_file(&client, &model, collection_name, filename, 2).await;
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn test_generate_embeddings() {
-        let model = initialize_model();
+        let model = initialize_model(&PipelineConfig::default());
         let documents = vec!["Document 1".to_string(), "Document 2".to_string()];
-        let embeddings = generate_embeddings(&model, documents);
+        let embeddings = generate_embeddings(&model, documents).await.expect("Failed to generate embeddings");
         assert_eq!(embeddings.len(), 2);
         assert!(embeddings[0].len() > 0);
     }
@@ -296,28 +739,52 @@ mod tests {
     async fn test_upsert_points() {
         let client = Qdrant::from_url("http://localhost:6334").build().expect("Failed to build Qdrant client");
         let collection_name = "real_estate";
-        let model = initialize_model();
+        let model = initialize_model(&PipelineConfig::default());
 
         let documents = vec!["Document 1".to_string(), "Document 2".to_string()];
-        let embeddings = generate_embeddings(&model, documents);
+        let embeddings = generate_embeddings(&model, documents.clone()).await.expect("Failed to generate embeddings");
         let payloads = vec![
             json!({"description": "Document 1", "key": "value1"}),
             json!({"description": "Document 2", "key": "value2"})
         ];
 
-        let result = upsert_points(&client, collection_name, embeddings, payloads).await;
+        let result = upsert_points(&client, collection_name, 0, &documents, embeddings, payloads).await;
         assert!(result.is_ok());
     }
 
     #[tokio::test]
     async fn test_search_qdrant() {
         let client = Qdrant::from_url("http://localhost:6334").build().expect("Failed to build Qdrant client");
-        let model = initialize_model();
+        let model = initialize_model(&PipelineConfig::default());
         let collection_name = "real_estate";
         let query = "test query";
 
         let result = search_qdrant(&client, &model, collection_name, query).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_setup_query_cache_collection() {
+        let client = Qdrant::from_url("http://localhost:6334").build().expect("Failed to build Qdrant client");
+        let result = setup_query_cache_collection(&client, &PipelineConfig::default()).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_search_with_cache_miss_then_hit() {
+        let client = Qdrant::from_url("http://localhost:6334").build().expect("Failed to build Qdrant client");
+        let model = initialize_model(&PipelineConfig::default());
+        let collection_name = "real_estate";
+        let query = "test query for cache";
+
+        setup_query_cache_collection(&client, &PipelineConfig::default()).await.expect("Failed to set up query cache collection");
+
+        let first = search_with_cache(&client, &model, collection_name, query, 0.95).await;
+        assert!(first.is_ok());
+
+        let second = search_with_cache(&client, &model, collection_name, query, 0.95).await;
+        assert!(second.is_ok());
+        assert_eq!(first.unwrap(), second.unwrap());
+    }
 }
 
@@ -1,31 +1,75 @@
 // main.rs
 
+mod config;
 mod controller;
+mod rag;
+mod retry;
+mod sources;
+mod sparse;
 
-use controller::{initialize_model, load_data, setup_qdrant_collection, generate_embeddings, upsert_points, search_qdrant};
-use qdrant_client::{Qdrant, QdrantError};
+use config::PipelineConfig;
+use controller::{
+    generate_embeddings, ingest_file, initialize_model, search_with_cache, setup_qdrant_collection,
+    setup_query_cache_collection, upsert_points, PipelineError,
+};
+use qdrant_client::Qdrant;
+use sources::source_for_path;
 use tokio;
 
+/// Default text column used when the source file is a CSV and no column is given
+/// as the second CLI argument; ignored for JSONL/Markdown sources.
+const DEFAULT_TEXT_COLUMN: &str = "description";
+
+/// How many lines [`ingest_file`] embeds and upserts per batch for the JSONL path.
+const INGEST_BATCH_SIZE: usize = 256;
+
+/// Minimum cosine score for [`search_with_cache`] to treat a query as a cache hit.
+const CACHE_SCORE_THRESHOLD: f32 = 0.95;
+
 #[tokio::main]
-async fn main() -> Result<(), QdrantError> {
+async fn main() -> Result<(), PipelineError> {
+    let config = PipelineConfig::default();
+
     // Initialize model and client
-    let model = initialize_model();
+    let model = initialize_model(&config);
     let client = Qdrant::from_url("http://localhost:6334").build()?;
 
-    // Setup Qdrant collection
-    setup_qdrant_collection(&client).await?;
+    // Setup the main collection and the query-result cache collection
+    setup_qdrant_collection(&client, &config).await?;
+    setup_query_cache_collection(&client, &config).await?;
 
-    // Load data from file
-    let (documents, payloads) = load_data("data.jsonl");
+    let path = std::env::args().nth(1).unwrap_or_else(|| "data.jsonl".to_string());
+    let text_column = std::env::args().nth(2).unwrap_or_else(|| DEFAULT_TEXT_COLUMN.to_string());
 
-    // Generate embeddings
-    let embeddings = generate_embeddings(&model, documents);
+    // JSONL files go through ingest_file, which embeds and upserts them batch by batch
+    // (the whole file is still held in memory); CSV/Markdown sources are loaded,
+    // embedded and upserted in one shot.
+    if path.ends_with(".csv") || path.ends_with(".md") {
+        let (documents, payloads) = source_for_path(&path, &text_column).load();
+        let embeddings = generate_embeddings(&model, documents.clone()).await?;
+        upsert_points(&client, &config.collection_name, 0, &documents, embeddings, payloads).await?;
+    } else {
+        ingest_file(&client, &model, &config.collection_name, &path, INGEST_BATCH_SIZE).await?;
+    }
 
-    // Upsert points into Qdrant
-    upsert_points(&client, "real_estate", embeddings, payloads).await?;
+    // Search in Qdrant, serving repeated queries from the cache when possible
+    let results = search_with_cache(
+        &client,
+        &model,
+        &config.collection_name,
+        "detached house in cul de sac",
+        CACHE_SCORE_THRESHOLD,
+    )
+    .await?;
 
-    // Search in Qdrant
-    search_qdrant(&client, &model, "real_estate", "detached house in cul de sac").await?;
+    if let Some(payload) = results.into_iter().next() {
+        if let Some(description) = payload.get("description") {
+            println!("Found description: {}", description);
+        }
+        if let Some(link) = payload.get("link") {
+            println!("Found link: {}", link);
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,97 @@
+// rag.rs
+
+//! Retrieval-augmented generation: retrieve the top-K payloads for a query, then
+//! ask a chat-completion backend to answer using only that context.
+
+use crate::controller::{fetch_search_results, PipelineError};
+use fastembed::TextEmbedding;
+use qdrant_client::Qdrant;
+use serde_json::Value;
+
+/// A pluggable chat-completion backend for [`rag_query`].
+///
+/// Implementations can wrap a local model, a hosted API, or a test double, so
+/// `rag_query`'s retrieval path stays decoupled from which LLM answers the question.
+pub trait Completion {
+    /// Sends `prompt` to the backend and returns the generated answer.
+    fn complete(&self, prompt: &str) -> Result<String, anyhow::Error>;
+}
+
+/// The result of a [`rag_query`] call: the generated answer plus the source
+/// payloads it was grounded in, so callers can show citations.
+pub struct RagAnswer {
+    pub answer: String,
+    pub sources: Vec<Value>,
+}
+
+/// Retrieves the top-`k` payloads for `query`, builds a context block from their
+/// `description` fields, and asks `completion` to answer using only that context.
+///
+/// # Arguments
+///
+/// * `client` - A reference to the `Qdrant` client.
+/// * `model` - A reference to the `TextEmbedding` model.
+/// * `collection_name` - The name of the collection to retrieve from.
+/// * `query` - The question to answer.
+/// * `k` - How many source payloads to retrieve and include as context.
+/// * `completion` - The chat-completion backend to generate the answer with.
+///
+/// # Returns
+///
+/// The generated answer together with the source payloads used as context.
+///
+/// # Example
+///
+/// ```
+/// let answer = rag_query(&client, &model, "real_estate", "what's near the station?", 5, &completion).await?;
+/// println!("{}", answer.answer);
+/// ```
+pub async fn rag_query(
+    client: &Qdrant,
+    model: &TextEmbedding,
+    collection_name: &str,
+    query: &str,
+    k: usize,
+    completion: &impl Completion,
+) -> Result<RagAnswer, PipelineError> {
+    let sources = fetch_search_results(client, model, collection_name, query, k).await?;
+
+    let context = sources
+        .iter()
+        .filter_map(|payload| payload.get("description").and_then(|d| d.as_str()))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let prompt = format!(
+        "Answer the question using only the context below. If the context doesn't contain the answer, say you don't know.\n\n\
+         Context:\n{context}\n\nQuestion: {query}"
+    );
+
+    let answer = completion.complete(&prompt)?;
+
+    Ok(RagAnswer { answer, sources })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PipelineConfig;
+
+    struct EchoCompletion;
+
+    impl Completion for EchoCompletion {
+        fn complete(&self, prompt: &str) -> Result<String, anyhow::Error> {
+            Ok(format!("echo: {prompt}"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rag_query() {
+        let client = Qdrant::from_url("http://localhost:6334").build().expect("Failed to build Qdrant client");
+        let model = crate::controller::initialize_model(&PipelineConfig::default());
+
+        let result = rag_query(&client, &model, "real_estate", "detached house in cul de sac", 5, &EchoCompletion).await;
+        assert!(result.is_ok());
+        assert!(result.unwrap().answer.starts_with("echo:"));
+    }
+}
@@ -0,0 +1,182 @@
+// retry.rs
+
+//! Retry helper for transient embedding failures.
+//!
+//! Without a retry, a single failed FastEmbed call would abort the whole pipeline.
+//! This module gives callers a small strategy enum to classify a failure
+//! and a generic retry loop that backs off exponentially between attempts, so batch
+//! ingestion of large JSONL files can ride out transient errors instead of dying.
+
+use std::future::Future;
+use std::time::Duration;
+
+/// How a failed operation should be handled by [`with_retry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryStrategy {
+    /// The error is not transient; stop retrying and surface it to the caller.
+    GiveUp,
+    /// The error looks transient; retry after a short exponential backoff.
+    Retry,
+    /// The error indicates a rate limit; retry after a longer backoff.
+    RetryAfterRateLimit,
+}
+
+/// Classifies an embedding error into a [`RetryStrategy`].
+///
+/// FastEmbed surfaces failures as opaque [`anyhow::Error`] messages, so this falls
+/// back to matching on the text: anything mentioning a rate limit backs off longer,
+/// anything mentioning a timeout or temporary unavailability is retried normally, and
+/// anything else is treated as non-transient.
+pub fn classify_embedding_error(error: &anyhow::Error) -> RetryStrategy {
+    let message = error.to_string().to_lowercase();
+    if message.contains("rate limit") || message.contains("too many requests") {
+        RetryStrategy::RetryAfterRateLimit
+    } else if message.contains("timed out") || message.contains("timeout") || message.contains("temporarily") {
+        RetryStrategy::Retry
+    } else {
+        RetryStrategy::GiveUp
+    }
+}
+
+/// Runs `operation` up to `max_attempts` times in total, backing off between attempts
+/// according to the [`RetryStrategy`] that `classify` assigns to each failure.
+///
+/// Ordinary retries sleep `10^attempt` milliseconds; rate-limited retries sleep
+/// `100 + 10^attempt` milliseconds, giving the rate limit extra room to clear. A
+/// [`RetryStrategy::GiveUp`] classification, or exhausting `max_attempts`, returns
+/// the most recent error.
+///
+/// # Example
+///
+/// ```
+/// let embeddings = with_retry(5, classify_embedding_error, || async {
+///     model.embed(documents.clone(), None)
+/// }).await?;
+/// ```
+pub async fn with_retry<T, F, Fut>(
+    max_attempts: u32,
+    classify: impl Fn(&anyhow::Error) -> RetryStrategy,
+    mut operation: F,
+) -> Result<T, anyhow::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, anyhow::Error>>,
+{
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(error) => {
+                attempt += 1;
+                let strategy = classify(&error);
+                if attempt >= max_attempts || strategy == RetryStrategy::GiveUp {
+                    return Err(error);
+                }
+                tokio::time::sleep(Duration::from_millis(backoff_ms(strategy, attempt))).await;
+            }
+        }
+    }
+}
+
+/// Synchronous counterpart to [`with_retry`] for contexts that can't `.await` (e.g.
+/// a rayon worker thread embedding a batch in [`ingest_file`](crate::controller::ingest_file)).
+/// Identical backoff behavior, blocking the thread with [`std::thread::sleep`] instead.
+pub fn with_retry_blocking<T>(
+    max_attempts: u32,
+    classify: impl Fn(&anyhow::Error) -> RetryStrategy,
+    mut operation: impl FnMut() -> Result<T, anyhow::Error>,
+) -> Result<T, anyhow::Error> {
+    let mut attempt = 0;
+    loop {
+        match operation() {
+            Ok(value) => return Ok(value),
+            Err(error) => {
+                attempt += 1;
+                let strategy = classify(&error);
+                if attempt >= max_attempts || strategy == RetryStrategy::GiveUp {
+                    return Err(error);
+                }
+                std::thread::sleep(Duration::from_millis(backoff_ms(strategy, attempt)));
+            }
+        }
+    }
+}
+
+/// Backoff duration for the `attempt`'th retry under `strategy`, shared by
+/// [`with_retry`] and [`with_retry_blocking`].
+fn backoff_ms(strategy: RetryStrategy, attempt: u32) -> u64 {
+    match strategy {
+        RetryStrategy::RetryAfterRateLimit => 100 + 10u64.pow(attempt),
+        _ => 10u64.pow(attempt),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_classify_embedding_error() {
+        assert_eq!(
+            classify_embedding_error(&anyhow::anyhow!("429 rate limit exceeded")),
+            RetryStrategy::RetryAfterRateLimit
+        );
+        assert_eq!(
+            classify_embedding_error(&anyhow::anyhow!("request timed out")),
+            RetryStrategy::Retry
+        );
+        assert_eq!(
+            classify_embedding_error(&anyhow::anyhow!("invalid input shape")),
+            RetryStrategy::GiveUp
+        );
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_succeeds_after_transient_failures() {
+        let attempts = &Cell::new(0);
+        let result = with_retry(5, classify_embedding_error, || {
+            attempts.set(attempts.get() + 1);
+            async move {
+                if attempts.get() < 3 {
+                    Err(anyhow::anyhow!("temporarily unavailable"))
+                } else {
+                    Ok(42)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_gives_up_immediately_on_non_transient_error() {
+        let attempts = Cell::new(0);
+        let result: Result<(), anyhow::Error> = with_retry(5, classify_embedding_error, || {
+            attempts.set(attempts.get() + 1);
+            async { Err(anyhow::anyhow!("invalid input shape")) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_with_retry_blocking_succeeds_after_transient_failures() {
+        let mut attempts = 0;
+        let result = with_retry_blocking(5, classify_embedding_error, || {
+            attempts += 1;
+            if attempts < 3 {
+                Err(anyhow::anyhow!("temporarily unavailable"))
+            } else {
+                Ok(42)
+            }
+        });
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts, 3);
+    }
+}
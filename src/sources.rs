@@ -0,0 +1,204 @@
+// sources.rs
+
+//! Pluggable document sources for ingestion.
+//!
+//! [`load_data`](crate::controller::load_data) only understands JSONL files with a
+//! `description` field. [`DocumentSource`] generalizes that into a trait so CSV
+//! files and Markdown knowledge bases can feed the same embedding/upsert pipeline,
+//! turning this crate into a general-purpose ingestion tool rather than a
+//! real-estate-only demo.
+
+use serde_json::{json, Value};
+use std::fs;
+
+/// A source of documents (and their payloads) to ingest into Qdrant.
+///
+/// `load` returns the same `(documents, payloads)` shape as
+/// [`load_data`](crate::controller::load_data), so any implementation can be handed
+/// straight to [`generate_embeddings`](crate::controller::generate_embeddings) and
+/// [`upsert_points`](crate::controller::upsert_points).
+pub trait DocumentSource {
+    /// Loads every document and its payload from the source.
+    fn load(&self) -> (Vec<String>, Vec<Value>);
+}
+
+/// Loads documents from a JSONL file, one JSON object per line, using its
+/// `description` field as the document text. This is the original,
+/// real-estate-specific behavior of [`load_data`](crate::controller::load_data).
+pub struct JsonlSource {
+    pub path: String,
+}
+
+impl DocumentSource for JsonlSource {
+    fn load(&self) -> (Vec<String>, Vec<Value>) {
+        crate::controller::load_data(&self.path)
+    }
+}
+
+/// Loads documents from a CSV file: `text_column` becomes the document text, and
+/// every column (including `text_column`) is carried into the payload keyed by its
+/// header.
+pub struct CsvSource {
+    pub path: String,
+    pub text_column: String,
+}
+
+impl DocumentSource for CsvSource {
+    fn load(&self) -> (Vec<String>, Vec<Value>) {
+        let mut reader = csv::Reader::from_path(&self.path).expect("Unable to open CSV file");
+        let headers = reader.headers().expect("Unable to read CSV headers").clone();
+        let text_column_index = headers.iter().position(|header| header == self.text_column);
+
+        let mut documents = Vec::new();
+        let mut payloads = Vec::new();
+
+        for (index, result) in reader.records().enumerate() {
+            let record = result.expect("Unable to read CSV record");
+
+            let Some(text) = text_column_index.and_then(|column| record.get(column)) else {
+                eprintln!("Column '{}' not found for CSV row: {}", self.text_column, index);
+                continue;
+            };
+
+            let mut payload = serde_json::Map::new();
+            for (header, value) in headers.iter().zip(record.iter()) {
+                payload.insert(header.to_string(), json!(value));
+            }
+
+            documents.push(text.to_string());
+            payloads.push(Value::Object(payload));
+        }
+
+        (documents, payloads)
+    }
+}
+
+/// Loads documents from a Markdown knowledge-base file, splitting on headings: each
+/// heading's section becomes one document, with the file path and heading text
+/// carried in the payload.
+pub struct MarkdownSource {
+    pub path: String,
+}
+
+impl DocumentSource for MarkdownSource {
+    fn load(&self) -> (Vec<String>, Vec<Value>) {
+        let content = fs::read_to_string(&self.path).expect("Unable to read Markdown file");
+
+        let mut documents = Vec::new();
+        let mut payloads = Vec::new();
+        let mut current_heading = String::new();
+        let mut current_chunk = String::new();
+
+        for line in content.lines() {
+            if line.starts_with('#') {
+                push_markdown_chunk(&self.path, &current_heading, &current_chunk, &mut documents, &mut payloads);
+                current_heading = line.trim_start_matches('#').trim().to_string();
+                current_chunk.clear();
+            } else {
+                current_chunk.push_str(line);
+                current_chunk.push('\n');
+            }
+        }
+        push_markdown_chunk(&self.path, &current_heading, &current_chunk, &mut documents, &mut payloads);
+
+        (documents, payloads)
+    }
+}
+
+/// Pushes the accumulated Markdown section as one document, skipping blank sections
+/// (e.g. the text before the first heading, if the file starts with one).
+fn push_markdown_chunk(
+    path: &str,
+    heading: &str,
+    chunk: &str,
+    documents: &mut Vec<String>,
+    payloads: &mut Vec<Value>,
+) {
+    let text = chunk.trim();
+    if text.is_empty() {
+        return;
+    }
+
+    documents.push(text.to_string());
+    payloads.push(json!({
+        "description": text,
+        "path": path,
+        "heading": heading,
+    }));
+}
+
+/// Picks a [`DocumentSource`] implementation based on `path`'s file extension:
+/// `.csv` for [`CsvSource`], `.md` for [`MarkdownSource`], and everything else
+/// (including `.jsonl`) for [`JsonlSource`].
+///
+/// # Arguments
+///
+/// * `path` - The path to the source file.
+/// * `text_column` - The CSV text column to use; ignored for non-CSV sources.
+///
+/// # Example
+///
+/// ```
+/// let source = source_for_path("notes.md", "description");
+/// let (documents, payloads) = source.load();
+/// ```
+pub fn source_for_path(path: &str, text_column: &str) -> Box<dyn DocumentSource> {
+    match path.rsplit('.').next() {
+        Some("csv") => Box::new(CsvSource {
+            path: path.to_string(),
+            text_column: text_column.to_string(),
+        }),
+        Some("md") => Box::new(MarkdownSource { path: path.to_string() }),
+        _ => Box::new(JsonlSource { path: path.to_string() }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+
+    #[test]
+    fn test_csv_source_splits_text_column_from_payload() {
+        let filename = "test_source.csv";
+        let mut file = File::create(filename).expect("Unable to create file");
+        writeln!(file, "description,link").expect("Unable to write to file");
+        writeln!(file, "Detached house,http://example.com/1").expect("Unable to write to file");
+
+        let source = CsvSource {
+            path: filename.to_string(),
+            text_column: "description".to_string(),
+        };
+        let (documents, payloads) = source.load();
+
+        assert_eq!(documents, vec!["Detached house".to_string()]);
+        assert_eq!(payloads[0]["link"], json!("http://example.com/1"));
+    }
+
+    #[test]
+    fn test_markdown_source_chunks_by_heading() {
+        let filename = "test_source.md";
+        let mut file = File::create(filename).expect("Unable to create file");
+        writeln!(file, "# Intro\nWelcome text.\n# Details\nMore text.").expect("Unable to write to file");
+
+        let source = MarkdownSource { path: filename.to_string() };
+        let (documents, payloads) = source.load();
+
+        assert_eq!(documents.len(), 2);
+        assert_eq!(payloads[0]["heading"], json!("Intro"));
+        assert_eq!(payloads[1]["heading"], json!("Details"));
+    }
+
+    #[test]
+    fn test_source_for_path_picks_markdown_loader_for_md_extension() {
+        let filename = "test_source_for_path.md";
+        let mut file = File::create(filename).expect("Unable to create file");
+        writeln!(file, "# Heading\nBody text.").expect("Unable to write to file");
+
+        let (documents, payloads) = source_for_path(filename, "description").load();
+
+        assert_eq!(documents, vec!["Body text.".to_string()]);
+        assert_eq!(payloads[0]["heading"], json!("Heading"));
+    }
+}
@@ -0,0 +1,111 @@
+// sparse.rs
+
+//! Sparse (keyword) vectors and Reciprocal Rank Fusion for hybrid search.
+//!
+//! The dense embeddings produced by FastEmbed are great at semantic similarity but
+//! can miss exact-term queries such as street names or postcodes. This module builds
+//! a cheap term-frequency sparse vector for a piece of text, and fuses a dense and a
+//! sparse ranked list into a single ranking via Reciprocal Rank Fusion (RRF).
+
+use qdrant_client::qdrant::SparseVector;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// RRF constant `k`, controlling how quickly rank position decays a point's score.
+/// Larger values flatten the influence of rank; 60 is the commonly cited default.
+const RRF_K: f32 = 60.0;
+
+/// Splits `text` into lowercase alphanumeric terms.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| term.to_lowercase())
+        .collect()
+}
+
+/// Hashes a term to a `u32` sparse-vector index.
+///
+/// Collisions are possible but rare enough for a keyword signal used alongside
+/// dense embeddings; they just mean two distinct terms occasionally share a slot.
+fn term_index(term: &str) -> u32 {
+    let mut hasher = DefaultHasher::new();
+    term.hash(&mut hasher);
+    (hasher.finish() % u32::MAX as u64) as u32
+}
+
+/// Computes a raw term-frequency sparse vector for `text`.
+///
+/// Each distinct term becomes one `(index, value)` pair, where `index` is the
+/// hashed term and `value` is the number of times it occurs in `text`.
+///
+/// # Example
+///
+/// ```
+/// let sparse = compute_sparse_vector("123 Main Street, Main City");
+/// ```
+pub fn compute_sparse_vector(text: &str) -> SparseVector {
+    let mut term_frequencies: HashMap<u32, f32> = HashMap::new();
+    for term in tokenize(text) {
+        *term_frequencies.entry(term_index(&term)).or_insert(0.0) += 1.0;
+    }
+
+    let mut indices: Vec<u32> = term_frequencies.keys().copied().collect();
+    indices.sort_unstable();
+    let values = indices.iter().map(|index| term_frequencies[index]).collect();
+
+    SparseVector { indices, values }
+}
+
+/// Fuses a dense-search ranking and a sparse-search ranking with Reciprocal Rank
+/// Fusion, returning point IDs sorted by descending fused score.
+///
+/// For each list, a point at rank `r` (0-indexed) contributes `1 / (RRF_K + r + 1)`
+/// to its fused score; points appearing in both lists accumulate both contributions.
+///
+/// # Example
+///
+/// ```
+/// let fused = reciprocal_rank_fusion(&dense_ids, &sparse_ids);
+/// let top_10: Vec<u64> = fused.into_iter().take(10).map(|(id, _)| id).collect();
+/// ```
+pub fn reciprocal_rank_fusion(dense_ranked_ids: &[u64], sparse_ranked_ids: &[u64]) -> Vec<(u64, f32)> {
+    let mut scores: HashMap<u64, f32> = HashMap::new();
+
+    for (rank, &id) in dense_ranked_ids.iter().enumerate() {
+        *scores.entry(id).or_insert(0.0) += 1.0 / (RRF_K + rank as f32 + 1.0);
+    }
+    for (rank, &id) in sparse_ranked_ids.iter().enumerate() {
+        *scores.entry(id).or_insert(0.0) += 1.0 / (RRF_K + rank as f32 + 1.0);
+    }
+
+    let mut fused: Vec<(u64, f32)> = scores.into_iter().collect();
+    fused.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_sparse_vector_counts_term_frequency() {
+        let sparse = compute_sparse_vector("Main Street, Main City");
+        assert_eq!(sparse.indices.len(), sparse.values.len());
+        // "main" occurs twice and should be the only value of 2.0.
+        assert_eq!(sparse.values.iter().filter(|&&v| v == 2.0).count(), 1);
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion_rewards_agreement() {
+        let dense_ids = vec![1, 2, 3];
+        let sparse_ids = vec![2, 1, 4];
+
+        let fused = reciprocal_rank_fusion(&dense_ids, &sparse_ids);
+        let top_id = fused.first().expect("fused ranking should not be empty").0;
+
+        // Points 1 and 2 appear in both lists, so one of them should rank above
+        // point 3 or 4, which only appear in a single list.
+        assert!(top_id == 1 || top_id == 2);
+    }
+}